@@ -0,0 +1,154 @@
+use crate::types::{Error, FileInfo};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// What to do with the non-preferred members of a duplicate group once the
+/// keeper (`grp[0]` after sorting) has been chosen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    None,
+    Delete,
+    Hardlink,
+    Symlink,
+    Reflink,
+}
+
+impl Action {
+    fn verb(self) -> &'static str {
+        match self {
+            Action::None => "ignore",
+            Action::Delete => "delete",
+            Action::Hardlink => "hardlink",
+            Action::Symlink => "symlink",
+            Action::Reflink => "reflink",
+        }
+    }
+}
+
+impl FromStr for Action {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Action, Error> {
+        Ok(match s {
+            "none" => Action::None,
+            "delete" => Action::Delete,
+            "hardlink" => Action::Hardlink,
+            "symlink" => Action::Symlink,
+            "reflink" => Action::Reflink,
+            _ => return Err(Error::InvalidAction(s.to_string())),
+        })
+    }
+}
+
+/// A sibling path to write a replacement into before atomically renaming it
+/// over the original, so an interrupted run never leaves a half-written
+/// file in place of a duplicate.
+fn temp_sibling(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".rdupes-tmp");
+    path.with_file_name(name)
+}
+
+fn replace_with_link(member: &Path, keeper: &Path, symlink: bool) -> std::io::Result<()> {
+    let tmp = temp_sibling(member);
+    let _ = fs::remove_file(&tmp);
+    if symlink {
+        // Symlink targets resolve relative to the link's own parent
+        // directory, not the process cwd, so a relative `keeper` would
+        // point to the wrong place whenever it and `member` differ in
+        // directory. Canonicalize it to an absolute path first.
+        let target = keeper.canonicalize()?;
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(&target, &tmp)?;
+        #[cfg(windows)]
+        std::os::windows::fs::symlink_file(&target, &tmp)?;
+    } else {
+        fs::hard_link(keeper, &tmp)?;
+    }
+    fs::rename(&tmp, member)
+}
+
+fn reflink_replace(member: &Path, keeper: &Path) -> Result<(), Error> {
+    let tmp = temp_sibling(member);
+    let _ = fs::remove_file(&tmp);
+    reflink_copy::reflink(keeper, &tmp)?;
+    fs::rename(&tmp, member)?;
+    Ok(())
+}
+
+/// Apply `action` to one duplicate, stat-and-comparing against the keeper
+/// first so a file that changed since it was hashed is never touched. The
+/// keeper's and the member's current sizes must match each other, and each
+/// one's current mtime must match the mtime recorded in `FileInfo` at hash
+/// time, since a same-length in-place edit between the hashing pass and this
+/// one would otherwise slip through.
+fn apply_one(action: Action, keeper: &FileInfo, member: &FileInfo) -> Result<(), Error> {
+    let keeper_meta = keeper.path.metadata()?;
+    let member_meta = member.path.metadata()?;
+    if keeper_meta.len() != member_meta.len()
+        || keeper_meta.modified().ok() != keeper.mtime
+        || member_meta.modified().ok() != member.mtime
+    {
+        return Err(Error::StaleDuplicate(member.path.clone()));
+    }
+    match action {
+        Action::None => Ok(()),
+        Action::Delete => fs::remove_file(&member.path).map_err(Error::from),
+        Action::Hardlink => replace_with_link(&member.path, &keeper.path, false).map_err(Error::from),
+        Action::Symlink => replace_with_link(&member.path, &keeper.path, true).map_err(Error::from),
+        Action::Reflink => reflink_replace(&member.path, &keeper.path),
+    }
+}
+
+/// Apply `action` to every member of `group` after the keeper (`group[0]`,
+/// assumed already sorted by preference). Returns `(succeeded, failed)`.
+/// With `dry_run` set, nothing is touched; the action is only previewed,
+/// and each previewed member is counted as succeeded.
+pub fn apply_group(action: Action, dry_run: bool, group: &[&FileInfo]) -> (usize, usize) {
+    if action == Action::None || group.len() < 2 {
+        return (0, 0);
+    }
+    let keeper = group[0];
+    let mut succeeded = 0;
+    let mut failed = 0;
+    for member in &group[1..] {
+        if dry_run {
+            println!("  [dry-run] would {} {}", action.verb(), member.display());
+            succeeded += 1;
+            continue;
+        }
+        match apply_one(action, keeper, member) {
+            Ok(()) => succeeded += 1,
+            Err(e) => {
+                eprintln!(
+                    "error applying {} to {}: {}",
+                    action.verb(),
+                    member.display(),
+                    e
+                );
+                failed += 1;
+            }
+        }
+    }
+    (succeeded, failed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Action;
+    use std::str::FromStr;
+
+    #[test]
+    fn from_str_parses_known_actions() {
+        assert_eq!(Action::from_str("none").unwrap(), Action::None);
+        assert_eq!(Action::from_str("delete").unwrap(), Action::Delete);
+        assert_eq!(Action::from_str("hardlink").unwrap(), Action::Hardlink);
+        assert_eq!(Action::from_str("symlink").unwrap(), Action::Symlink);
+        assert_eq!(Action::from_str("reflink").unwrap(), Action::Reflink);
+    }
+
+    #[test]
+    fn from_str_rejects_unknown_action() {
+        assert!(Action::from_str("nuke").is_err());
+    }
+}
@@ -0,0 +1,218 @@
+use crate::hashing::Digest;
+use crate::types::Error;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// A previously-computed hash for a file, along with the `(size, mtime)`
+/// it was computed against so a stale entry can be detected cheaply.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheEntry {
+    pub size: u64,
+    pub mtime: Option<SystemTime>,
+    pub partial_hash: Option<Digest>,
+    pub full_hash: Option<Digest>,
+}
+
+/// On-disk cache of file hashes, keyed by canonical path, so re-running
+/// `rdupes` over an unchanged tree doesn't need to re-read every file.
+#[derive(Debug)]
+pub struct HashCache {
+    path: Option<PathBuf>,
+    entries: HashMap<PathBuf, CacheEntry>,
+    touched: HashSet<PathBuf>,
+}
+
+impl HashCache {
+    /// Default cache file location, under the platform's user cache dir.
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::cache_dir().map(|d| d.join("rdupes").join("hashes.bin"))
+    }
+
+    /// Load the cache from `path` (or [`HashCache::default_path`] if
+    /// `path` is `None`). A missing or unreadable cache file just starts
+    /// empty rather than erroring, since the cache is purely an
+    /// optimization.
+    pub fn load(path: Option<PathBuf>) -> Self {
+        let path = path.or_else(Self::default_path);
+        let entries = match path.as_ref().map(std::fs::read) {
+            None | Some(Err(_)) => HashMap::new(),
+            Some(Ok(data)) => bincode::deserialize(&data).unwrap_or_else(|e| {
+                eprintln!("warning: {}", Error::CacheLoad(e.to_string()));
+                HashMap::new()
+            }),
+        };
+        HashCache {
+            path,
+            entries,
+            touched: HashSet::new(),
+        }
+    }
+
+    /// Look up a cached entry for `path`, but only if it still matches the
+    /// file's current `size`/`mtime`. A hit is marked as touched so it
+    /// survives pruning on save.
+    pub fn lookup(
+        &mut self,
+        path: &Path,
+        size: u64,
+        mtime: Option<SystemTime>,
+    ) -> Option<CacheEntry> {
+        let hit = self
+            .entries
+            .get(path)
+            .filter(|e| e.size == size && e.mtime == mtime)
+            .cloned();
+        if hit.is_some() {
+            self.touched.insert(path.to_owned());
+        }
+        hit
+    }
+
+    /// Record a freshly computed hash for `path`, merging with whatever is
+    /// already cached for it (dropping any stale hash for a different
+    /// `size`/`mtime`).
+    pub fn store(
+        &mut self,
+        path: PathBuf,
+        size: u64,
+        mtime: Option<SystemTime>,
+        partial_hash: Option<Digest>,
+        full_hash: Option<Digest>,
+    ) {
+        let entry = self
+            .entries
+            .entry(path.clone())
+            .or_insert_with(|| CacheEntry {
+                size,
+                mtime,
+                partial_hash: None,
+                full_hash: None,
+            });
+        if entry.size != size || entry.mtime != mtime {
+            entry.size = size;
+            entry.mtime = mtime;
+            entry.partial_hash = None;
+            entry.full_hash = None;
+        }
+        if partial_hash.is_some() {
+            entry.partial_hash = partial_hash;
+        }
+        if full_hash.is_some() {
+            entry.full_hash = full_hash;
+        }
+        self.touched.insert(path);
+    }
+
+    /// Persist the entries that were looked up or stored this run, pruning
+    /// everything else (including records for paths that no longer exist).
+    pub fn save(&self) -> Result<(), Error> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let pruned: HashMap<&PathBuf, &CacheEntry> = self
+            .entries
+            .iter()
+            .filter(|(p, _)| self.touched.contains(*p))
+            .collect();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| Error::CacheSave(e.to_string()))?;
+        }
+        let data = bincode::serialize(&pruned).map_err(|e| Error::CacheSave(e.to_string()))?;
+        std::fs::write(path, data).map_err(|e| Error::CacheSave(e.to_string()))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HashCache;
+    use crate::hashing::Digest;
+    use std::time::{Duration, SystemTime};
+
+    fn mtime(secs: u64) -> Option<SystemTime> {
+        Some(SystemTime::UNIX_EPOCH + Duration::from_secs(secs))
+    }
+
+    #[test]
+    fn lookup_misses_when_absent() {
+        let mut cache = HashCache::load(None);
+        assert!(cache
+            .lookup(std::path::Path::new("/tmp/nope"), 1, mtime(1))
+            .is_none());
+    }
+
+    #[test]
+    fn store_then_lookup_hits_on_matching_size_and_mtime() {
+        let mut cache = HashCache::load(None);
+        let path = std::path::PathBuf::from("/tmp/a");
+        let hash = Digest::Crc32([1, 2, 3, 4]);
+        cache.store(path.clone(), 10, mtime(100), None, Some(hash));
+        let hit = cache.lookup(&path, 10, mtime(100)).unwrap();
+        assert_eq!(hit.full_hash, Some(hash));
+        assert_eq!(hit.partial_hash, None);
+    }
+
+    #[test]
+    fn lookup_misses_on_stale_size_or_mtime() {
+        let mut cache = HashCache::load(None);
+        let path = std::path::PathBuf::from("/tmp/a");
+        cache.store(path.clone(), 10, mtime(100), None, Some(Digest::Crc32([0; 4])));
+        assert!(cache.lookup(&path, 11, mtime(100)).is_none());
+        assert!(cache.lookup(&path, 10, mtime(101)).is_none());
+    }
+
+    #[test]
+    fn store_drops_stale_hashes_when_size_or_mtime_changes() {
+        let mut cache = HashCache::load(None);
+        let path = std::path::PathBuf::from("/tmp/a");
+        cache.store(path.clone(), 10, mtime(100), Some(Digest::Crc32([0; 4])), None);
+        // Re-stored with a different size: the stale partial hash must not
+        // survive alongside the new one.
+        cache.store(path.clone(), 20, mtime(200), None, Some(Digest::Crc32([1; 4])));
+        let hit = cache.lookup(&path, 20, mtime(200)).unwrap();
+        assert_eq!(hit.partial_hash, None);
+        assert_eq!(hit.full_hash, Some(Digest::Crc32([1; 4])));
+    }
+
+    #[test]
+    fn save_prunes_entries_that_were_never_touched_this_run() {
+        let dir = std::env::temp_dir().join(format!("rdupes-cache-test-{:?}", std::thread::current().id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let cache_path = dir.join("hashes.bin");
+
+        // A first run persists an entry that the second run never touches.
+        let mut first = HashCache::load(Some(cache_path.clone()));
+        first.store(
+            std::path::PathBuf::from("/tmp/untouched"),
+            1,
+            mtime(1),
+            None,
+            Some(Digest::Crc32([8; 4])),
+        );
+        first.save().unwrap();
+
+        let mut cache = HashCache::load(Some(cache_path.clone()));
+        cache.store(
+            std::path::PathBuf::from("/tmp/touched"),
+            1,
+            mtime(1),
+            None,
+            Some(Digest::Crc32([9; 4])),
+        );
+        cache.save().unwrap();
+
+        // Reload: only the entry touched by the second run should have been
+        // persisted.
+        let mut reloaded = HashCache::load(Some(cache_path.clone()));
+        assert!(reloaded
+            .lookup(std::path::Path::new("/tmp/touched"), 1, mtime(1))
+            .is_some());
+        assert!(reloaded
+            .lookup(std::path::Path::new("/tmp/untouched"), 1, mtime(1))
+            .is_none());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
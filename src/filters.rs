@@ -0,0 +1,81 @@
+use std::path::Path;
+
+/// Directory/extension exclusion rules applied during traversal.
+#[derive(Debug, Clone, Default)]
+pub struct Filters {
+    /// Globs matched against the full path of every entry seen while
+    /// walking; a match prunes the entry (and, for a directory, the whole
+    /// subtree under it) before it's ever stat'd.
+    pub exclude_globs: Vec<glob::Pattern>,
+    /// If set, only files with one of these extensions (lowercase, no
+    /// leading dot) are considered.
+    pub ext_allow: Option<Vec<String>>,
+    /// Files with one of these extensions (lowercase, no leading dot) are
+    /// skipped even if they pass `ext_allow`.
+    pub ext_deny: Vec<String>,
+}
+
+impl Filters {
+    /// Whether `path` should be pruned from the walk entirely.
+    pub fn excludes_path(&self, path: &Path) -> bool {
+        self.exclude_globs.iter().any(|p| p.matches_path(path))
+    }
+
+    /// Whether a file's extension passes the allow/deny lists.
+    pub fn ext_allowed(&self, path: &Path) -> bool {
+        let ext = path
+            .extension()
+            .map(|e| e.to_string_lossy().to_lowercase());
+        let allowed = self.ext_allow.as_ref().is_none_or(|allow| {
+            ext.as_deref().is_some_and(|e| allow.iter().any(|a| a == e))
+        });
+        let not_denied = ext
+            .as_deref()
+            .is_none_or(|e| !self.ext_deny.iter().any(|d| d == e));
+        allowed && not_denied
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Filters;
+    use std::path::Path;
+
+    #[test]
+    fn ext_allowed_with_no_filters_allows_everything() {
+        let filters = Filters::default();
+        assert!(filters.ext_allowed(Path::new("a.txt")));
+        assert!(filters.ext_allowed(Path::new("a")));
+    }
+
+    #[test]
+    fn ext_allowed_respects_allow_list_case_insensitively() {
+        let filters = Filters {
+            ext_allow: Some(vec!["txt".to_string()]),
+            ..Filters::default()
+        };
+        assert!(filters.ext_allowed(Path::new("a.TXT")));
+        assert!(!filters.ext_allowed(Path::new("a.jpg")));
+        assert!(!filters.ext_allowed(Path::new("a")));
+    }
+
+    #[test]
+    fn ext_allowed_respects_deny_list_even_when_allowed() {
+        let filters = Filters {
+            ext_allow: Some(vec!["txt".to_string()]),
+            ext_deny: vec!["txt".to_string()],
+            ..Filters::default()
+        };
+        assert!(!filters.ext_allowed(Path::new("a.txt")));
+    }
+
+    #[test]
+    fn excludes_path_matches_globs() {
+        let filters = Filters {
+            exclude_globs: vec![glob::Pattern::new("**/node_modules/**").unwrap()],
+            ..Filters::default()
+        };
+        assert!(filters.excludes_path(Path::new("proj/node_modules/foo.js")));
+        assert!(!filters.excludes_path(Path::new("proj/src/foo.js")));
+    }
+}
@@ -0,0 +1,150 @@
+use serde::{Deserialize, Serialize};
+use std::io::{self, Read};
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::types::Error;
+
+/// Which hash function to use when comparing candidate duplicates.
+/// BLAKE3 is the default, trading some speed for cryptographic strength;
+/// the others are there for users who trust their local data and just
+/// want the fastest possible way to find same-content files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HashAlgo {
+    Blake3,
+    Xxh3,
+    Crc32,
+}
+
+impl FromStr for HashAlgo {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<HashAlgo, Error> {
+        Ok(match s {
+            "blake3" => HashAlgo::Blake3,
+            "xxh3" => HashAlgo::Xxh3,
+            "crc32" => HashAlgo::Crc32,
+            _ => return Err(Error::InvalidHashAlgo(s.to_string())),
+        })
+    }
+}
+
+/// A digest produced by one of the supported [`HashAlgo`]s. Kept as an enum
+/// rather than a fixed-width array since the algorithms don't all produce
+/// the same number of bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Digest {
+    Blake3([u8; 32]),
+    Xxh3([u8; 8]),
+    Crc32([u8; 4]),
+}
+
+impl Digest {
+    pub fn algo(&self) -> HashAlgo {
+        match self {
+            Digest::Blake3(_) => HashAlgo::Blake3,
+            Digest::Xxh3(_) => HashAlgo::Xxh3,
+            Digest::Crc32(_) => HashAlgo::Crc32,
+        }
+    }
+}
+
+const BUF_SIZE: usize = 64 * 1024;
+
+fn hash_reader_xxh3<R: Read>(mut reader: R) -> io::Result<Digest> {
+    let mut hasher = xxhash_rust::xxh3::Xxh3::new();
+    let mut buf = [0u8; BUF_SIZE];
+    loop {
+        let n = reader.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(Digest::Xxh3(hasher.digest().to_le_bytes()))
+}
+
+fn hash_reader_crc32<R: Read>(mut reader: R) -> io::Result<Digest> {
+    let mut hasher = crc32fast::Hasher::new();
+    let mut buf = [0u8; BUF_SIZE];
+    loop {
+        let n = reader.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(Digest::Crc32(hasher.finalize().to_le_bytes()))
+}
+
+/// Hash the whole file at `path` with `algo`. BLAKE3 uses a memory-mapped,
+/// multi-threaded hash for speed; the other algorithms fall back to
+/// buffered block reads since they have no such acceleration in their
+/// respective crates.
+pub fn hash_path(path: &Path, algo: HashAlgo) -> io::Result<Digest> {
+    match algo {
+        HashAlgo::Blake3 => {
+            let mut hasher = blake3::Hasher::new();
+            hasher.update_mmap_rayon(path)?;
+            Ok(Digest::Blake3(*hasher.finalize().as_bytes()))
+        }
+        HashAlgo::Xxh3 => hash_reader_xxh3(std::fs::File::open(path)?),
+        HashAlgo::Crc32 => hash_reader_crc32(std::fs::File::open(path)?),
+    }
+}
+
+/// Hash just the leading `bytes` of the file at `path` with `algo`.
+pub fn hash_path_partial(path: &Path, bytes: u64, algo: HashAlgo) -> io::Result<Digest> {
+    let file = std::fs::File::open(path)?.take(bytes);
+    match algo {
+        HashAlgo::Blake3 => {
+            let mut hasher = blake3::Hasher::new();
+            io::copy(&mut { file }, &mut hasher)?;
+            Ok(Digest::Blake3(*hasher.finalize().as_bytes()))
+        }
+        HashAlgo::Xxh3 => hash_reader_xxh3(file),
+        HashAlgo::Crc32 => hash_reader_crc32(file),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{hash_reader_crc32, hash_reader_xxh3, HashAlgo};
+    use std::str::FromStr;
+
+    #[test]
+    fn hash_algo_from_str_parses_known_values() {
+        assert_eq!(HashAlgo::from_str("blake3").unwrap(), HashAlgo::Blake3);
+        assert_eq!(HashAlgo::from_str("xxh3").unwrap(), HashAlgo::Xxh3);
+        assert_eq!(HashAlgo::from_str("crc32").unwrap(), HashAlgo::Crc32);
+    }
+
+    #[test]
+    fn hash_algo_from_str_rejects_unknown_value() {
+        assert!(HashAlgo::from_str("md5").is_err());
+    }
+
+    #[test]
+    fn digest_algo_matches_the_variant() {
+        assert_eq!(
+            super::Digest::Blake3([0; 32]).algo(),
+            HashAlgo::Blake3
+        );
+        assert_eq!(super::Digest::Xxh3([0; 8]).algo(), HashAlgo::Xxh3);
+        assert_eq!(super::Digest::Crc32([0; 4]).algo(), HashAlgo::Crc32);
+    }
+
+    #[test]
+    fn same_content_hashes_equal_across_reads() {
+        let a = hash_reader_xxh3(b"hello world".as_slice()).unwrap();
+        let b = hash_reader_xxh3(b"hello world".as_slice()).unwrap();
+        let c = hash_reader_xxh3(b"goodbye world".as_slice()).unwrap();
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+
+        let a = hash_reader_crc32(b"hello world".as_slice()).unwrap();
+        let b = hash_reader_crc32(b"hello world".as_slice()).unwrap();
+        let c = hash_reader_crc32(b"goodbye world".as_slice()).unwrap();
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+    }
+}
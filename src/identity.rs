@@ -0,0 +1,17 @@
+use std::fs::Metadata;
+
+/// `(device, inode)` — identifies the underlying file a path's hardlinks
+/// all point to. Two paths with the same identity share storage, so
+/// "deduplicating" one against the other frees no disk space.
+pub type Identity = (u64, u64);
+
+#[cfg(unix)]
+pub fn file_identity(meta: &Metadata) -> Option<Identity> {
+    use std::os::unix::fs::MetadataExt;
+    Some((meta.dev(), meta.ino()))
+}
+
+#[cfg(not(unix))]
+pub fn file_identity(_meta: &Metadata) -> Option<Identity> {
+    None
+}
@@ -1,18 +1,30 @@
-use blake3::{Hash, Hasher};
 use clap::{arg, command, value_parser, Arg};
 use rayon::prelude::*;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use std::io::{self, Write};
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
 use std::vec::Vec;
 
+mod actions;
+mod cache;
+mod filters;
+mod hashing;
+mod identity;
+mod report;
 mod sorting;
 mod types;
 
+use actions::Action;
+use cache::HashCache;
+use filters::Filters;
+use hashing::{Digest, HashAlgo};
+use identity::file_identity;
+use report::OutputFormat;
 use sorting::{SortKeys, SortOptions};
 use types::{Error, FileInfo};
 
@@ -22,25 +34,38 @@ struct Options {
     follow_symlinks: bool,
     min_size: u64,
     max_depth: Option<u64>,
+    partial_bytes: u64,
+    cache_path: Option<PathBuf>,
+    action: Action,
+    dry_run: bool,
+    hash_algo: HashAlgo,
+    filters: Filters,
     sort_options: SortOptions,
+    ignore_hardlinks: bool,
+    format: OutputFormat,
 }
 
 fn find_same_sized_files<I>(
     paths: I,
     table: &mut HashMap<u64, Vec<FileInfo>>,
     options: &Options,
-) -> Result<(usize, usize, usize), Error>
+) -> Result<(usize, usize, usize, usize), Error>
 where
     I: Iterator<Item = Result<(usize, PathBuf), Error>>,
 {
     let mut files = 0;
     let mut seen = 0;
     let mut skipped = 0;
+    let mut skipped_ext = 0;
     for item in paths {
         let (depth, path) = item?;
         seen += 1;
         if path.is_file() {
             files += 1;
+            if !options.filters.ext_allowed(&path) {
+                skipped_ext += 1;
+                continue;
+            }
             let metadata = path.metadata()?;
             let size = metadata.len();
             if size >= options.min_size {
@@ -48,6 +73,9 @@ where
                     depth,
                     mtime: metadata.modified().ok(),
                     path,
+                    partial_hash: None,
+                    full_hash: None,
+                    inode: file_identity(&metadata),
                 };
                 match table.get_mut(&size) {
                     None => {
@@ -65,39 +93,165 @@ where
             }
         }
     }
-    Ok((seen, files, skipped))
+    Ok((seen, files, skipped, skipped_ext))
+}
+
+/// Compute (or reuse from `cache`) the partial hash for `path`. A cached
+/// entry is only used if it was computed with the same `algo`, since
+/// digests from different algorithms aren't comparable.
+fn cached_partial_hash(
+    path: &Path,
+    size: u64,
+    mtime: Option<SystemTime>,
+    bytes: u64,
+    algo: HashAlgo,
+    cache: &Mutex<HashCache>,
+) -> io::Result<Digest> {
+    let canonical = path.canonicalize()?;
+    if let Some(h) = cache
+        .lock()
+        .unwrap()
+        .lookup(&canonical, size, mtime)
+        .and_then(|e| e.partial_hash)
+        .filter(|h| h.algo() == algo)
+    {
+        return Ok(h);
+    }
+    let hash = hashing::hash_path_partial(path, bytes, algo)?;
+    cache
+        .lock()
+        .unwrap()
+        .store(canonical, size, mtime, Some(hash), None);
+    Ok(hash)
 }
 
-fn hash_path(path: &Path) -> io::Result<Hash> {
-    let mut hasher = Hasher::new();
-    hasher.update_mmap_rayon(path)?;
-    Ok(hasher.finalize())
+/// Compute (or reuse from `cache`) the full hash for `path`.
+fn cached_full_hash(
+    path: &Path,
+    size: u64,
+    mtime: Option<SystemTime>,
+    algo: HashAlgo,
+    cache: &Mutex<HashCache>,
+) -> io::Result<Digest> {
+    let canonical = path.canonicalize()?;
+    if let Some(h) = cache
+        .lock()
+        .unwrap()
+        .lookup(&canonical, size, mtime)
+        .and_then(|e| e.full_hash)
+        .filter(|h| h.algo() == algo)
+    {
+        return Ok(h);
+    }
+    let hash = hashing::hash_path(path, algo)?;
+    cache
+        .lock()
+        .unwrap()
+        .store(canonical, size, mtime, None, Some(hash));
+    Ok(hash)
 }
 
+/// Full-hash every file in `paths` (skipping the cache-hit ones) and group
+/// the results. Used directly when the partial-hash stage is disabled or
+/// would provide no benefit (the file is no bigger than the prefix).
+fn full_hash_group<'a>(
+    paths: &'a mut [FileInfo],
+    size: u64,
+    options: &Options,
+    cache: &Mutex<HashCache>,
+    hash_count: &mut usize,
+) -> Result<Vec<Vec<&'a FileInfo>>, Error> {
+    let algo = options.hash_algo;
+    let results: Vec<io::Result<()>> = paths
+        .par_iter_mut()
+        .map(|fi| {
+            let h = cached_full_hash(&fi.path, size, fi.mtime, algo, cache)?;
+            fi.full_hash = Some(h);
+            Ok(())
+        })
+        .collect();
+    *hash_count += results.len();
+    for r in results {
+        r?;
+    }
+    let mut matches: HashMap<Digest, Vec<&FileInfo>> = HashMap::new();
+    for fi in paths.iter() {
+        if let Some(h) = fi.full_hash {
+            matches.entry(h).or_default().push(fi);
+        }
+    }
+    Ok(matches.into_values().filter(|x| x.len() > 1).collect())
+}
+
+/// Find duplicates within a same-size group. When `options.partial_bytes` is
+/// non-zero and less than `size`, files are first grouped by a cheap hash
+/// over just their leading `partial_bytes` bytes, so a full (whole-file) hash
+/// is only computed for sub-groups whose partial hashes actually collide.
+/// Hashes are looked up in (and written back to) `cache` so unchanged files
+/// don't need to be re-read on the next run.
 fn find_duplicates<'a>(
-    paths: &'a [FileInfo],
+    paths: &'a mut [FileInfo],
+    size: u64,
+    options: &Options,
+    cache: &Mutex<HashCache>,
     hash_count: &mut usize,
 ) -> Result<Vec<Vec<&'a FileInfo>>, Error> {
-    let mut matches: HashMap<_, Vec<&FileInfo>> = HashMap::new();
-    let mut hashes: Vec<_> = paths
-        .par_iter()
-        .map(|i| hash_path(i).map(|h| (*h.as_bytes(), i)))
+    let partial_bytes = options.partial_bytes;
+    let algo = options.hash_algo;
+    if partial_bytes == 0 || size <= partial_bytes {
+        return full_hash_group(paths, size, options, cache, hash_count);
+    }
+    let results: Vec<io::Result<()>> = paths
+        .par_iter_mut()
+        .map(|fi| {
+            let h = cached_partial_hash(&fi.path, size, fi.mtime, partial_bytes, algo, cache)?;
+            fi.partial_hash = Some(h);
+            Ok(())
+        })
         .collect();
-    *hash_count = hashes.len();
-    for i in hashes.drain(..) {
-        let (h, p) = i?;
-        if let Some(existing) = matches.get_mut(&h) {
-            existing.push(p);
-        } else {
-            matches.insert(h, vec![p]);
+    for r in results {
+        r?;
+    }
+    let mut partial_counts: HashMap<Digest, usize> = HashMap::new();
+    for fi in paths.iter() {
+        if let Some(h) = fi.partial_hash {
+            *partial_counts.entry(h).or_insert(0) += 1;
         }
     }
-    let r = matches
-        .drain()
-        .map(|x| x.1)
-        .filter(|x| x.len() > 1)
+    let results: Vec<io::Result<()>> = paths
+        .par_iter_mut()
+        .filter(|fi| {
+            fi.partial_hash
+                .is_some_and(|h| partial_counts.get(&h).copied().unwrap_or(0) > 1)
+        })
+        .map(|fi| {
+            let h = cached_full_hash(&fi.path, size, fi.mtime, algo, cache)?;
+            fi.full_hash = Some(h);
+            Ok(())
+        })
         .collect();
-    Ok(r)
+    *hash_count += results.len();
+    for r in results {
+        r?;
+    }
+    let mut matches: HashMap<Digest, Vec<&FileInfo>> = HashMap::new();
+    for fi in paths.iter() {
+        if let Some(h) = fi.full_hash {
+            matches.entry(h).or_default().push(fi);
+        }
+    }
+    Ok(matches.into_values().filter(|x| x.len() > 1).collect())
+}
+
+/// Number of logically distinct files in `grp`, treating paths that share
+/// an inode (i.e. are already hardlinked to each other) as one. Files
+/// without a known identity (non-Unix platforms) are always counted as
+/// distinct.
+fn distinct_file_count(grp: &[&FileInfo]) -> usize {
+    let mut seen = HashSet::new();
+    grp.iter()
+        .filter(|fi| fi.inode.is_none_or(|id| seen.insert(id)))
+        .count()
 }
 
 fn run<I, J>(dirs: I, options: &Options) -> Result<(), Error>
@@ -110,6 +264,10 @@ where
     let num_groups = Arc::new(AtomicUsize::new(0));
     let num_errors = Arc::new(AtomicUsize::new(0));
     let total_sz = Arc::new(AtomicU64::new(0));
+    let num_action_ok = Arc::new(AtomicUsize::new(0));
+    let num_action_failed = Arc::new(AtomicUsize::new(0));
+    let next_group_id = AtomicUsize::new(0);
+    let reports: Mutex<Vec<report::ReportGroup>> = Mutex::new(Vec::new());
     let depth = if options.recurse {
         options.max_depth
     } else {
@@ -119,6 +277,8 @@ where
     let mut seen_counter = 0;
     let mut files_counter = 0;
     let mut skipped_counter = 0;
+    let mut skipped_ext_counter = 0;
+    let excluded_counter = std::cell::Cell::new(0usize);
     for dir in dirs {
         let mut iter = walkdir::WalkDir::new(dir);
         if let Some(d) = depth {
@@ -129,18 +289,28 @@ where
         }
         let i = iter
             .into_iter()
+            .filter_entry(|e| {
+                if options.filters.excludes_path(e.path()) {
+                    excluded_counter.set(excluded_counter.get() + 1);
+                    false
+                } else {
+                    true
+                }
+            })
             .map(|d| d.map(|e| (e.depth(), e.into_path())).map_err(Error::from));
-        let (seen, files, skipped) = find_same_sized_files(i, &mut table, options)?;
+        let (seen, files, skipped, skipped_ext) = find_same_sized_files(i, &mut table, options)?;
         seen_counter += seen;
         files_counter += files;
         skipped_counter += skipped;
+        skipped_ext_counter += skipped_ext;
     }
-    table.par_drain().for_each(|(sz, paths)| {
+    let cache = Mutex::new(HashCache::load(options.cache_path.clone()));
+    table.par_drain().for_each(|(sz, mut paths)| {
         if paths.len() < 2 {
             return;
         }
         let mut hash_count = 0;
-        let x = find_duplicates(&paths, &mut hash_count);
+        let x = find_duplicates(&mut paths, sz, options, &cache, &mut hash_count);
         num_hashes.fetch_add(hash_count, Ordering::Relaxed);
         match x {
             Err(e) => {
@@ -148,29 +318,70 @@ where
                 num_errors.fetch_add(1, Ordering::Relaxed);
             }
             Ok(mut paths) => {
-                num_groups.fetch_add(paths.len(), Ordering::Relaxed);
                 let stdout = std::io::stdout();
                 for grp in paths.iter_mut() {
+                    grp.sort_unstable_by(|l, r| options.sort_options.cmp_for_fileinfos(l, r));
+                    if options.ignore_hardlinks {
+                        let mut seen = HashSet::new();
+                        grp.retain(|fi| fi.inode.is_none_or(|id| seen.insert(id)));
+                        if grp.len() < 2 {
+                            continue;
+                        }
+                    }
+                    num_groups.fetch_add(1, Ordering::Relaxed);
                     let grplen = grp.len();
                     num_duplicates.fetch_add(grplen, Ordering::Relaxed);
-                    total_sz.fetch_add(sz * (grplen as u64 - 1), Ordering::Relaxed);
-                    grp.sort_unstable_by(|l, r| options.sort_options.cmp_for_fileinfos(l, r));
-                    let mut out = stdout.lock();
-                    let _ = writeln!(out, "\u{250C} {:?} bytes", sz);
-                    for (k, p) in grp.iter().enumerate() {
-                        if k < grplen - 1 {
-                            let _ = writeln!(out, "\u{251C} {}", p.display());
-                        } else {
-                            let _ = writeln!(out, "\u{2514} {}\n", p.display());
+                    let distinct = distinct_file_count(grp);
+                    if distinct > 1 {
+                        total_sz.fetch_add(sz * (distinct as u64 - 1), Ordering::Relaxed);
+                    }
+                    match options.format {
+                        OutputFormat::Tree => {
+                            let mut out = stdout.lock();
+                            let _ = writeln!(out, "\u{250C} {:?} bytes", sz);
+                            for (k, p) in grp.iter().enumerate() {
+                                if k < grplen - 1 {
+                                    let _ = writeln!(out, "\u{251C} {}", p.display());
+                                } else {
+                                    let _ = writeln!(out, "\u{2514} {}\n", p.display());
+                                }
+                            }
+                        }
+                        OutputFormat::Json | OutputFormat::Csv => {
+                            let id = next_group_id.fetch_add(1, Ordering::Relaxed);
+                            reports.lock().unwrap().push(report::group(id, sz, grp));
                         }
                     }
+                    let (ok, failed) = actions::apply_group(options.action, options.dry_run, grp);
+                    num_action_ok.fetch_add(ok, Ordering::Relaxed);
+                    num_action_failed.fetch_add(failed, Ordering::Relaxed);
                 }
             }
         }
     });
+    if let Err(e) = cache.into_inner().unwrap().save() {
+        eprintln!("warning: could not save hash cache: {}", e);
+    }
+    match options.format {
+        OutputFormat::Tree => {}
+        OutputFormat::Json => {
+            let reports = reports.into_inner().unwrap();
+            report::write_json(&mut io::stdout(), &reports).map_err(|e| Error::ReportWrite(e.to_string()))?;
+        }
+        OutputFormat::Csv => {
+            let reports = reports.into_inner().unwrap();
+            report::write_csv(io::stdout(), &reports).map_err(|e| Error::ReportWrite(e.to_string()))?;
+        }
+    }
     let summary1 = format!(
-        "{} regular files seen (of {} files total), {} skipped by min-size ({}B).",
-        files_counter, seen_counter, skipped_counter, options.min_size
+        "{} regular files seen (of {} files total), {} skipped by min-size ({}B), \
+         {} skipped by extension, {} excluded by --exclude.",
+        files_counter,
+        seen_counter,
+        skipped_counter,
+        options.min_size,
+        skipped_ext_counter,
+        excluded_counter.get(),
     );
     let summary2 = format!(
         "{} total candidate files hashed, {} errors. {} duplicates over {} groups. {} wasted bytes.",
@@ -180,7 +391,25 @@ where
         num_groups.load(Ordering::SeqCst),
         total_sz.load(Ordering::SeqCst),
     );
-    println!("{}\n{}", summary1, summary2);
+    // For the structured formats, the summary is informational only and
+    // must not mix with the machine-readable data on stdout.
+    let print_summary = |msg: &str| {
+        if options.format == OutputFormat::Tree {
+            println!("{}", msg);
+        } else {
+            eprintln!("{}", msg);
+        }
+    };
+    print_summary(&format!("{}\n{}", summary1, summary2));
+    if options.action != Action::None {
+        print_summary(&format!(
+            "{} duplicates {}, {} failed ({}).",
+            num_action_ok.load(Ordering::SeqCst),
+            if options.dry_run { "would be acted on" } else { "acted on" },
+            num_action_failed.load(Ordering::SeqCst),
+            if options.dry_run { "dry run" } else { "applied" },
+        ));
+    }
     Ok(())
 }
 
@@ -200,6 +429,39 @@ fn main() {
             arg!(--"max-depth" <DEPTH> "maximum depth to recurse (0 is no recursion). implies -r.")
                 .value_parser(value_parser!(u64)),
         )
+        .arg(
+            arg!(--"partial-bytes" <BYTES> "bytes of each file to prefix-hash before a full hash is attempted (0 disables)")
+                .value_parser(value_parser!(u64))
+                .default_value("4096"),
+        )
+        .arg(
+            arg!(--cache <PATH> "path to the persistent hash cache (defaults to the user cache dir)")
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .arg(
+            arg!(--action <MODE> "what to do with duplicates once a keeper is chosen: none, delete, hardlink, symlink, reflink")
+                .value_parser(Action::from_str)
+                .default_value("none"),
+        )
+        .arg(
+            arg!(--"no-dry-run" "actually perform the chosen action instead of only previewing it"),
+        )
+        .arg(
+            arg!(--hash <ALGO> "hash algorithm to compare candidate duplicates with: blake3, xxh3, crc32")
+                .value_parser(HashAlgo::from_str)
+                .default_value("blake3"),
+        )
+        .arg(
+            arg!(--exclude <GLOB> "skip paths matching this glob (repeatable)")
+                .action(clap::ArgAction::Append)
+                .value_parser(value_parser!(String)),
+        )
+        .arg(
+            arg!(--ext <EXTS> "only consider files with one of these comma-separated extensions"),
+        )
+        .arg(
+            arg!(--"exclude-ext" <EXTS> "skip files with one of these comma-separated extensions"),
+        )
         .arg(
             arg!(--"sort-by" <PROPS> "properties to sort by, comma-separated. depth,mtime,path")
                 .value_parser(SortKeys::from_str),
@@ -208,6 +470,14 @@ fn main() {
             arg!(--"prefer-within" <PATH> "prefer files within this path")
                 .value_parser(value_parser!(PathBuf)),
         )
+        .arg(
+            arg!(--"ignore-hardlinks" "drop paths already hardlinked to another member of a group instead of just reporting them"),
+        )
+        .arg(
+            arg!(--format <FORMAT> "report format: tree, json, csv")
+                .value_parser(OutputFormat::from_str)
+                .default_value("tree"),
+        )
         .arg(
             Arg::new("directory")
                 .required(true)
@@ -220,6 +490,39 @@ fn main() {
     let follow_symlinks = matches.get_flag("follow");
     let min_size: u64 = matches.get_one("min-size").copied().unwrap_or(1);
     let max_depth = matches.get_one::<u64>("max-depth").copied();
+    let partial_bytes: u64 = matches.get_one("partial-bytes").copied().unwrap_or(4096);
+    let cache_path = matches.get_one::<PathBuf>("cache").cloned();
+    let action = matches.get_one::<Action>("action").copied().unwrap_or(Action::None);
+    let dry_run = !matches.get_flag("no-dry-run");
+    let hash_algo = matches
+        .get_one::<HashAlgo>("hash")
+        .copied()
+        .unwrap_or(HashAlgo::Blake3);
+    let exclude_globs: Vec<glob::Pattern> = matches
+        .get_many::<String>("exclude")
+        .map(|vals| {
+            vals.map(|s| glob::Pattern::new(s).expect("invalid --exclude glob"))
+                .collect()
+        })
+        .unwrap_or_default();
+    let ext_allow: Option<Vec<String>> = matches.get_one::<String>("ext").map(|s| {
+        s.split(',')
+            .map(|x| x.trim().trim_start_matches('.').to_lowercase())
+            .collect()
+    });
+    let ext_deny: Vec<String> = matches
+        .get_one::<String>("exclude-ext")
+        .map(|s| {
+            s.split(',')
+                .map(|x| x.trim().trim_start_matches('.').to_lowercase())
+                .collect()
+        })
+        .unwrap_or_default();
+    let filters = Filters {
+        exclude_globs,
+        ext_allow,
+        ext_deny,
+    };
     let prefer_location = matches.get_one::<PathBuf>("prefer-within").map(|p| p.canonicalize().expect("could not canonicalize path"));
     let sort_by = matches
         .get_one::<SortKeys>("sort-opts")
@@ -229,6 +532,11 @@ fn main() {
         prefer_location,
         sort_by,
     };
+    let ignore_hardlinks = matches.get_flag("ignore-hardlinks");
+    let format = matches
+        .get_one::<OutputFormat>("format")
+        .copied()
+        .unwrap_or(OutputFormat::Tree);
     let result = run(
         dirs,
         &Options {
@@ -236,7 +544,15 @@ fn main() {
             follow_symlinks,
             min_size,
             max_depth,
+            partial_bytes,
+            cache_path,
+            action,
+            dry_run,
+            hash_algo,
+            filters,
             sort_options,
+            ignore_hardlinks,
+            format,
         },
     );
     if let Err(e) = result {
@@ -244,3 +560,128 @@ fn main() {
         std::process::exit(1);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{distinct_file_count, find_duplicates, full_hash_group, Options};
+    use crate::actions::Action;
+    use crate::filters::Filters;
+    use crate::sorting::{SortKeys, SortOptions};
+    use crate::types::FileInfo;
+    use std::sync::Mutex;
+
+    fn file(path: &str, inode: Option<(u64, u64)>) -> FileInfo {
+        FileInfo {
+            depth: 0,
+            mtime: None,
+            path: path.into(),
+            partial_hash: None,
+            full_hash: None,
+            inode,
+        }
+    }
+
+    #[test]
+    fn distinct_file_count_counts_files_without_identity_separately() {
+        let a = file("/a", None);
+        let b = file("/b", None);
+        assert_eq!(distinct_file_count(&[&a, &b]), 2);
+    }
+
+    #[test]
+    fn distinct_file_count_collapses_shared_inodes() {
+        let a = file("/a", Some((1, 1)));
+        let b = file("/b", Some((1, 1)));
+        let c = file("/c", Some((1, 2)));
+        assert_eq!(distinct_file_count(&[&a, &b, &c]), 2);
+    }
+
+    fn test_options(partial_bytes: u64) -> Options {
+        Options {
+            recurse: false,
+            follow_symlinks: false,
+            min_size: 0,
+            max_depth: None,
+            partial_bytes,
+            cache_path: None,
+            action: Action::None,
+            dry_run: true,
+            hash_algo: crate::hashing::HashAlgo::Blake3,
+            filters: Filters::default(),
+            sort_options: SortOptions {
+                prefer_location: None,
+                sort_by: SortKeys::default(),
+            },
+            ignore_hardlinks: false,
+            format: crate::report::OutputFormat::Tree,
+        }
+    }
+
+    fn scratch_dir(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "rdupes-main-test-{}-{:?}",
+            name,
+            std::thread::current().id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn write_file(dir: &std::path::Path, name: &str, content: &[u8]) -> FileInfo {
+        let path = dir.join(name);
+        std::fs::write(&path, content).unwrap();
+        let meta = path.metadata().unwrap();
+        FileInfo {
+            depth: 0,
+            mtime: meta.modified().ok(),
+            path,
+            partial_hash: None,
+            full_hash: None,
+            inode: None,
+        }
+    }
+
+    #[test]
+    fn full_hash_group_groups_identical_content_and_drops_singletons() {
+        let dir = scratch_dir("full-hash");
+        let mut files = vec![
+            write_file(&dir, "a", b"hello"),
+            write_file(&dir, "b", b"hello"),
+            write_file(&dir, "c", b"world"),
+        ];
+        let options = test_options(0);
+        let cache = Mutex::new(crate::cache::HashCache::load(None));
+        let mut hash_count = 0;
+        let groups = full_hash_group(&mut files, 5, &options, &cache, &mut hash_count).unwrap();
+        assert_eq!(hash_count, 3);
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].len(), 2);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn find_duplicates_only_promotes_partial_hash_collisions_and_verifies_full_content() {
+        let dir = scratch_dir("staged-hash");
+        // Same leading bytes, different tail: a colliding partial hash must
+        // not be enough to call these duplicates once fully hashed.
+        // "d" has a unique prefix, so it must never be promoted to a full hash.
+        let mut files = vec![
+            write_file(&dir, "a", b"prefixAAAA"),
+            write_file(&dir, "b", b"prefixBBBB"),
+            write_file(&dir, "c", b"prefixAAAA"),
+            write_file(&dir, "d", b"PREFIXAAAA"),
+        ];
+        let options = test_options(6);
+        let cache = Mutex::new(crate::cache::HashCache::load(None));
+        let mut hash_count = 0;
+        let groups = find_duplicates(&mut files, 10, &options, &cache, &mut hash_count).unwrap();
+        assert_eq!(hash_count, 3);
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].len(), 2);
+        let paths: Vec<_> = groups[0].iter().map(|fi| fi.path.clone()).collect();
+        assert!(paths.iter().any(|p| p.ends_with("a")));
+        assert!(paths.iter().any(|p| p.ends_with("c")));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
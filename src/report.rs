@@ -0,0 +1,177 @@
+use crate::types::{Error, FileInfo};
+use serde::Serialize;
+use std::io::{self, Write};
+use std::str::FromStr;
+use std::time::UNIX_EPOCH;
+
+/// Shape of the duplicate-group report written to stdout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// The original box-drawing tree, one block per group.
+    Tree,
+    /// One JSON object per line (size plus an ordered array of file entries).
+    Json,
+    /// One CSV row per file, with a group id column.
+    Csv,
+}
+
+impl FromStr for OutputFormat {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<OutputFormat, Error> {
+        Ok(match s {
+            "tree" => OutputFormat::Tree,
+            "json" => OutputFormat::Json,
+            "csv" => OutputFormat::Csv,
+            _ => return Err(Error::InvalidFormat(s.to_string())),
+        })
+    }
+}
+
+/// A single file within a reported duplicate group.
+#[derive(Debug, Serialize)]
+pub struct ReportEntry {
+    pub path: String,
+    pub depth: usize,
+    pub mtime_unix: Option<u64>,
+    /// Whether this is the member `SortOptions::cmp_for_fileinfos` ranked
+    /// first, i.e. the one every other member would be deduplicated against.
+    pub keeper: bool,
+}
+
+/// A duplicate group, ready to be serialized as one JSON object.
+#[derive(Debug, Serialize)]
+pub struct ReportGroup {
+    pub id: usize,
+    pub size: u64,
+    pub files: Vec<ReportEntry>,
+}
+
+/// One file of a duplicate group, flattened into a single CSV row.
+#[derive(Debug, Serialize)]
+struct CsvRow<'a> {
+    group_id: usize,
+    size: u64,
+    path: &'a str,
+    depth: usize,
+    mtime_unix: Option<u64>,
+    keeper: bool,
+}
+
+fn report_entry(fi: &FileInfo, keeper: bool) -> ReportEntry {
+    ReportEntry {
+        path: fi.path.display().to_string(),
+        depth: fi.depth,
+        mtime_unix: fi
+            .mtime
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_secs()),
+        keeper,
+    }
+}
+
+/// Build the report for one duplicate group. `grp` must already be sorted,
+/// so its first entry is the keeper.
+pub fn group(id: usize, size: u64, grp: &[&FileInfo]) -> ReportGroup {
+    let files = grp
+        .iter()
+        .enumerate()
+        .map(|(k, fi)| report_entry(fi, k == 0))
+        .collect();
+    ReportGroup { id, size, files }
+}
+
+/// Write every collected group to `out` as newline-delimited JSON.
+pub fn write_json<W: Write>(out: &mut W, groups: &[ReportGroup]) -> io::Result<()> {
+    for g in groups {
+        let line = serde_json::to_string(g).expect("ReportGroup is always serializable");
+        writeln!(out, "{}", line)?;
+    }
+    Ok(())
+}
+
+/// Write every collected group to `out` as CSV, one row per file.
+pub fn write_csv<W: Write>(out: W, groups: &[ReportGroup]) -> Result<(), csv::Error> {
+    let mut wtr = csv::Writer::from_writer(out);
+    for g in groups {
+        for f in &g.files {
+            wtr.serialize(CsvRow {
+                group_id: g.id,
+                size: g.size,
+                path: &f.path,
+                depth: f.depth,
+                mtime_unix: f.mtime_unix,
+                keeper: f.keeper,
+            })?;
+        }
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{group, write_csv, write_json, OutputFormat};
+    use crate::types::FileInfo;
+    use std::str::FromStr;
+
+    fn file(path: &str, depth: usize) -> FileInfo {
+        FileInfo {
+            depth,
+            mtime: None,
+            path: path.into(),
+            partial_hash: None,
+            full_hash: None,
+            inode: None,
+        }
+    }
+
+    #[test]
+    fn format_from_str_parses_known_values_and_rejects_others() {
+        assert_eq!(OutputFormat::from_str("tree").unwrap(), OutputFormat::Tree);
+        assert_eq!(OutputFormat::from_str("json").unwrap(), OutputFormat::Json);
+        assert_eq!(OutputFormat::from_str("csv").unwrap(), OutputFormat::Csv);
+        assert!(OutputFormat::from_str("xml").is_err());
+    }
+
+    #[test]
+    fn group_marks_only_the_first_entry_as_keeper() {
+        let a = file("/a", 0);
+        let b = file("/b", 1);
+        let g = group(7, 42, &[&a, &b]);
+        assert_eq!(g.id, 7);
+        assert_eq!(g.size, 42);
+        assert!(g.files[0].keeper);
+        assert!(!g.files[1].keeper);
+        assert_eq!(g.files[0].path, "/a");
+        assert_eq!(g.files[1].path, "/b");
+    }
+
+    #[test]
+    fn write_json_emits_one_line_per_group() {
+        let a = file("/a", 0);
+        let b = file("/b", 0);
+        let groups = vec![group(0, 10, &[&a, &b])];
+        let mut out = Vec::new();
+        write_json(&mut out, &groups).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 1);
+        let parsed: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(parsed["size"], 10);
+        assert_eq!(parsed["files"].as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn write_csv_emits_one_row_per_file_with_group_id() {
+        let a = file("/a", 0);
+        let b = file("/b", 0);
+        let groups = vec![group(3, 10, &[&a, &b])];
+        let mut out = Vec::new();
+        write_csv(&mut out, &groups).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        let rows: Vec<&str> = text.lines().skip(1).collect();
+        assert_eq!(rows.len(), 2);
+        assert!(rows[0].starts_with("3,10,/a,"));
+        assert!(rows[1].starts_with("3,10,/b,"));
+    }
+}
@@ -1,3 +1,5 @@
+use crate::hashing::Digest;
+use crate::identity::Identity;
 use std::fmt;
 use std::path::{Display, Path, PathBuf};
 use std::time::SystemTime;
@@ -7,6 +9,15 @@ pub struct FileInfo {
     pub depth: usize,
     pub mtime: Option<SystemTime>,
     pub path: PathBuf,
+    /// Hash over just the leading bytes of the file, filled in lazily (and
+    /// possibly reused from the on-disk cache) while grouping duplicates.
+    pub partial_hash: Option<Digest>,
+    /// Hash over the whole file, filled in lazily the same way.
+    pub full_hash: Option<Digest>,
+    /// `(dev, ino)` of the file, used to recognize paths that are already
+    /// hardlinked to each other. `None` on platforms where this isn't
+    /// available.
+    pub inode: Option<Identity>,
 }
 
 impl FileInfo {
@@ -38,6 +49,20 @@ pub enum Error {
     InvalidSortKey(String),
     #[error("Duplicate sort keys provided")]
     DuplicateSortKeys,
+    #[error("Failed to read hash cache: {0}")]
+    CacheLoad(String),
+    #[error("Failed to write hash cache: {0}")]
+    CacheSave(String),
+    #[error("Invalid action: {0}")]
+    InvalidAction(String),
+    #[error("Invalid hash algorithm: {0}")]
+    InvalidHashAlgo(String),
+    #[error("{0} changed since it was hashed, refusing to touch it")]
+    StaleDuplicate(PathBuf),
+    #[error("Invalid output format: {0}")]
+    InvalidFormat(String),
+    #[error("Failed to write report: {0}")]
+    ReportWrite(String),
 }
 
 impl From<walkdir::Error> for Error {